@@ -0,0 +1,111 @@
+//! An incremental builder for [`Logger`](../struct.Logger.html).
+
+use log::{LevelFilter, SetLoggerError};
+
+use {platform_init, Destination, Filter, Format, Logger, Theme, TimestampFormat};
+
+/// Builds a `Logger` by setting only the fields that differ from the
+/// defaults, then registering it globally with `init`.
+///
+/// ```no_run
+/// extern crate log;
+/// extern crate pretty_logger;
+///
+/// use log::LevelFilter;
+/// use pretty_logger::LoggerBuilder;
+///
+/// LoggerBuilder::new()
+///     .level(LevelFilter::Debug)
+///     .init()
+///     .unwrap();
+/// ```
+pub struct LoggerBuilder {
+    destination: Destination,
+    filter: Filter,
+    format: Format,
+    theme: Theme,
+    timestamp: Option<TimestampFormat>,
+}
+
+impl LoggerBuilder {
+    /// Creates a builder with the same defaults as
+    /// [`Logger::default`](../struct.Logger.html#impl-Default).
+    pub fn new() -> LoggerBuilder {
+        let destination = Destination::default();
+        let theme = if destination.isatty() {
+            Theme::default()
+        } else {
+            Theme::empty()
+        };
+
+        LoggerBuilder {
+            destination,
+            filter: Filter::default(),
+            format: Format::default(),
+            theme,
+            timestamp: None,
+        }
+    }
+
+    /// Sets the destination to log to.
+    pub fn destination(mut self, destination: Destination) -> LoggerBuilder {
+        self.destination = destination;
+        self
+    }
+
+    /// Sets a single global level, discarding any per-module filtering
+    /// configured so far.
+    pub fn level(mut self, level: LevelFilter) -> LoggerBuilder {
+        self.filter = Filter::from_level(level);
+        self
+    }
+
+    /// Sets per-module filtering directives.
+    pub fn filter(mut self, filter: Filter) -> LoggerBuilder {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets the theme to style output with.
+    pub fn theme(mut self, theme: Theme) -> LoggerBuilder {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the line format to use.
+    pub fn format(mut self, format: Format) -> LoggerBuilder {
+        self.format = format;
+        self
+    }
+
+    /// Enables a timestamp prefix, rendered with the given format.
+    pub fn timestamp(mut self, format: TimestampFormat) -> LoggerBuilder {
+        self.timestamp = Some(format);
+        self
+    }
+
+    /// Builds the `Logger`, without registering it globally.
+    pub fn build(self) -> Logger {
+        let mut logger =
+            Logger::with_filter(self.destination, self.filter, self.theme)
+                .with_format(self.format);
+
+        if let Some(timestamp) = self.timestamp {
+            logger = logger.with_timestamp(timestamp);
+        }
+
+        logger
+    }
+
+    /// Builds the `Logger` and registers it as the global logger.
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        platform_init();
+        self.build().set_logger()
+    }
+}
+
+impl Default for LoggerBuilder {
+    fn default() -> LoggerBuilder {
+        LoggerBuilder::new()
+    }
+}
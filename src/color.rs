@@ -0,0 +1,56 @@
+//! Cross-platform colored output, built on `termcolor`.
+//!
+//! `ansi_term`'s `Style::paint` always emits raw ANSI escape sequences,
+//! which not every Windows console understands. `termcolor` instead picks
+//! the right mechanism (ANSI escapes, the Windows console API, or nothing)
+//! for the writer it's given, so styling is routed through it rather than
+//! painted by hand.
+
+use std::io;
+
+use ansi_term::{Colour, Style};
+use termcolor::{Color, ColorSpec, WriteColor};
+
+/// Converts an `ansi_term` `Style` into the equivalent `termcolor`
+/// `ColorSpec`.
+pub(crate) fn to_color_spec(style: Style) -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    spec.set_fg(style.foreground.map(to_color));
+    spec.set_bg(style.background.map(to_color));
+    spec.set_bold(style.is_bold);
+    spec.set_dimmed(style.is_dimmed);
+    spec.set_italic(style.is_italic);
+    spec.set_underline(style.is_underline);
+    spec.set_strikethrough(style.is_strikethrough);
+    spec
+}
+
+/// Writes `text` to `writer` with `style` applied, resetting afterwards.
+///
+/// `reset` always runs, even if setting the color or writing `text` fails,
+/// so a mid-line I/O error can't leave the writer stuck in a styled state.
+pub(crate) fn write_styled<W: WriteColor + ?Sized>(
+    writer: &mut W,
+    style: Style,
+    text: &str,
+) -> io::Result<()> {
+    let result = writer
+        .set_color(&to_color_spec(style))
+        .and_then(|_| write!(writer, "{}", text));
+    writer.reset().and(result)
+}
+
+fn to_color(colour: Colour) -> Color {
+    match colour {
+        Colour::Black => Color::Black,
+        Colour::Red => Color::Red,
+        Colour::Green => Color::Green,
+        Colour::Yellow => Color::Yellow,
+        Colour::Blue => Color::Blue,
+        Colour::Purple => Color::Magenta,
+        Colour::Cyan => Color::Cyan,
+        Colour::White => Color::White,
+        Colour::Fixed(n) => Color::Ansi256(n),
+        Colour::RGB(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
@@ -0,0 +1,158 @@
+//! Per-module log filtering.
+
+use log::{Level, LevelFilter};
+
+/// A single directive parsed from a filter spec, e.g. `my_crate::net=trace`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Directive {
+    /// The module path this directive applies to, or `None` for the
+    /// default directive.
+    pub target: Option<String>,
+
+    /// The level this directive enables.
+    pub level: LevelFilter,
+}
+
+/// A set of directives used to decide whether a given record should be
+/// logged, based on its target and level.
+///
+/// Filters are built from directive strings like
+/// `my_crate=debug,my_crate::net=trace,warn`, in the same style as
+/// `env_logger`:
+///
+///  - An entry with no `=` that names a level (e.g. `warn`) sets the
+///    default level.
+///  - An entry with no `=` that names a path (e.g. `my_crate::net`) enables
+///    all levels for that path.
+///  - A `path=level` pair sets the level for that path.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    directives: Vec<Directive>,
+}
+
+impl Filter {
+    /// Parses a directive string into a `Filter`.
+    pub fn parse(spec: &str) -> Filter {
+        let mut directives = spec
+            .split(',')
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .map(Filter::parse_directive)
+            .collect::<Vec<_>>();
+
+        // Sort by descending target length, so matching at log time is a
+        // simple first-hit scan for the longest matching prefix.
+        directives.sort_by(|a, b| {
+            let a_len = a.target.as_ref().map_or(0, String::len);
+            let b_len = b.target.as_ref().map_or(0, String::len);
+            b_len.cmp(&a_len)
+        });
+
+        Filter { directives }
+    }
+
+    fn parse_directive(part: &str) -> Directive {
+        let mut pieces = part.splitn(2, '=');
+        let first = pieces.next().unwrap_or("").trim();
+        match pieces.next() {
+            Some(level) => Directive {
+                target: Some(first.to_owned()),
+                level: level.trim().parse().unwrap_or(LevelFilter::Off),
+            },
+            None => match first.parse() {
+                Ok(level) => Directive {
+                    target: None,
+                    level,
+                },
+                Err(_) => Directive {
+                    target: Some(first.to_owned()),
+                    level: LevelFilter::Trace,
+                },
+            },
+        }
+    }
+
+    /// Returns a `Filter` that only applies a single, global level.
+    pub fn from_level(level: LevelFilter) -> Filter {
+        Filter {
+            directives: vec![Directive { target: None, level }],
+        }
+    }
+
+    /// Returns whether a record with the given target and level should be
+    /// logged.
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        for directive in &self.directives {
+            let matches = match directive.target {
+                Some(ref prefix) => Filter::matches(target, prefix),
+                None => true,
+            };
+
+            if matches {
+                return directive
+                    .level
+                    .to_level()
+                    .map(|allowed| level <= allowed)
+                    .unwrap_or(false);
+            }
+        }
+
+        false
+    }
+
+    /// Returns whether `target` is `prefix` or one of its path segments,
+    /// e.g. `prefix` matches `prefix::sub` but not `prefixed`.
+    fn matches(target: &str, prefix: &str) -> bool {
+        target.starts_with(prefix) &&
+            (target.len() == prefix.len() ||
+                 target.as_bytes()[prefix.len()] == b':')
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Filter {
+        Filter::from_level(LevelFilter::Info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directive_matches_only_on_path_segment_boundaries() {
+        let filter = Filter::parse("net=trace,warn");
+
+        assert!(filter.enabled("net", Level::Trace));
+        assert!(filter.enabled("net::socket", Level::Trace));
+        assert!(!filter.enabled("network", Level::Debug));
+        assert!(filter.enabled("network", Level::Warn));
+    }
+
+    #[test]
+    fn longest_matching_target_wins() {
+        let filter = Filter::parse("a=debug,a::b=trace");
+
+        // `a::b` is more specific than `a`, so it should win even though
+        // `a` also matches.
+        assert!(filter.enabled("a::b", Level::Trace));
+        // `a::c` only matches the less specific `a` directive.
+        assert!(filter.enabled("a::c", Level::Debug));
+        assert!(!filter.enabled("a::c", Level::Trace));
+    }
+
+    #[test]
+    fn bare_level_sets_the_default_directive() {
+        let filter = Filter::parse("warn");
+
+        assert!(filter.enabled("anything", Level::Warn));
+        assert!(!filter.enabled("anything", Level::Info));
+    }
+
+    #[test]
+    fn nothing_matching_denies() {
+        let filter = Filter::parse("a::b=trace");
+
+        assert!(!filter.enabled("unrelated", Level::Error));
+    }
+}
@@ -0,0 +1,126 @@
+//! Composable line formats.
+
+/// One piece of a log line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FormatPiece {
+    /// The timestamp prefix, followed by its own `|` separator. Renders as
+    /// nothing if the logger has no timestamp format configured.
+    Timestamp,
+
+    /// The styled level name, e.g. `ERROR`.
+    Level,
+
+    /// The record's module path, padded to the width of the widest module
+    /// path seen so far.
+    Module,
+
+    /// The record's target, preceded by its own separator. Renders as
+    /// nothing if the target is the same as the module.
+    Target,
+
+    /// The formatted log message.
+    Args,
+
+    /// A literal string, copied verbatim.
+    Literal(String),
+}
+
+/// An ordered list of [`FormatPiece`](enum.FormatPiece.html)s describing how
+/// to lay out a log line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Format {
+    pieces: Vec<FormatPiece>,
+}
+
+impl Format {
+    /// Returns the pieces that make up this format, in the order they
+    /// should be rendered.
+    pub fn pieces(&self) -> &[FormatPiece] {
+        &self.pieces
+    }
+}
+
+impl Default for Format {
+    /// The layout used before `Format` existed: `{level}|{module}|{args}`,
+    /// with an extra `|{target}` when the target differs from the module,
+    /// and an optional timestamp prefix.
+    fn default() -> Format {
+        FormatBuilder::new()
+            .timestamp()
+            .level()
+            .literal("|")
+            .module()
+            .target()
+            .literal("|")
+            .args()
+            .build()
+    }
+}
+
+/// Builds a [`Format`](struct.Format.html) by chaining pieces together.
+///
+/// ```no_run
+/// use pretty_logger::FormatBuilder;
+///
+/// let format = FormatBuilder::new()
+///     .level()
+///     .literal(" [")
+///     .module()
+///     .literal("] ")
+///     .args()
+///     .build();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FormatBuilder {
+    pieces: Vec<FormatPiece>,
+}
+
+impl FormatBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> FormatBuilder {
+        FormatBuilder { pieces: Vec::new() }
+    }
+
+    /// Appends the timestamp piece.
+    pub fn timestamp(mut self) -> FormatBuilder {
+        self.pieces.push(FormatPiece::Timestamp);
+        self
+    }
+
+    /// Appends the level piece.
+    pub fn level(mut self) -> FormatBuilder {
+        self.pieces.push(FormatPiece::Level);
+        self
+    }
+
+    /// Appends the module piece.
+    pub fn module(mut self) -> FormatBuilder {
+        self.pieces.push(FormatPiece::Module);
+        self
+    }
+
+    /// Appends the target piece.
+    pub fn target(mut self) -> FormatBuilder {
+        self.pieces.push(FormatPiece::Target);
+        self
+    }
+
+    /// Appends the args piece.
+    pub fn args(mut self) -> FormatBuilder {
+        self.pieces.push(FormatPiece::Args);
+        self
+    }
+
+    /// Appends a literal string piece.
+    pub fn literal<S: Into<String>>(mut self, literal: S) -> FormatBuilder {
+        self.pieces.push(FormatPiece::Literal(literal.into()));
+        self
+    }
+
+    /// Builds the `Format`.
+    pub fn build(self) -> Format {
+        Format {
+            pieces: self.pieces,
+        }
+    }
+}
@@ -8,45 +8,137 @@
 #![deny(missing_docs)]
 
 extern crate ansi_term;
+extern crate chrono;
 extern crate isatty;
 extern crate log;
+extern crate termcolor;
 extern crate unicode_segmentation;
 
+mod builder;
+mod color;
+mod filter;
+mod format;
+mod timestamp;
+
+pub use builder::LoggerBuilder;
+pub use filter::{Directive, Filter};
+pub use format::{Format, FormatBuilder, FormatPiece};
+pub use timestamp::TimestampFormat;
+
 use std::cmp::max;
-use std::io::{stderr, stdout, Write};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
-use ansi_term::{ANSIGenericString, Colour, Style};
+use ansi_term::{Colour, Style};
 use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use termcolor::{ColorChoice, NoColor, StandardStream, WriteColor};
 use unicode_segmentation::UnicodeSegmentation;
 
+/// The colors a [`Theme`](struct.Theme.html) chooses per-target colors
+/// from.
+const PALETTE: [Colour; 8] = [
+    Colour::Black,
+    Colour::Red,
+    Colour::Green,
+    Colour::Yellow,
+    Colour::Blue,
+    Colour::Purple,
+    Colour::Cyan,
+    Colour::White,
+];
+
+/// A `Write` that forwards to a shared, lockable writer.
+///
+/// This lets a user-supplied `Destination::Writer` be wrapped in the same
+/// `termcolor` machinery as every other destination.
+struct SharedWriter(Arc<Mutex<dyn Write + Send>>);
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.lock().unwrap().flush()
+    }
+}
+
 /// Where to log errors to.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone)]
 pub enum Destination {
     /// Standard output
     Stdout,
 
     /// Standard error
     Stderr,
+
+    /// A file, opened in append mode.
+    File(PathBuf),
+
+    /// An arbitrary shared writer, e.g. an in-memory buffer used in tests.
+    Writer(Arc<Mutex<dyn Write + Send>>),
+}
+
+impl fmt::Debug for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Destination::Stdout => f.write_str("Destination::Stdout"),
+            Destination::Stderr => f.write_str("Destination::Stderr"),
+            Destination::File(ref path) => {
+                f.debug_tuple("Destination::File").field(path).finish()
+            }
+            Destination::Writer(_) => f.write_str("Destination::Writer(..)"),
+        }
+    }
 }
 
 impl Destination {
     /// Returns whether the given destination is a TTY.
+    ///
+    /// Files and arbitrary writers are never treated as a TTY, so loggers
+    /// writing to them automatically disable color.
     pub fn isatty(&self) -> bool {
         match *self {
             Destination::Stdout => isatty::stdout_isatty(),
             Destination::Stderr => isatty::stderr_isatty(),
+            Destination::File(_) | Destination::Writer(_) => false,
         }
     }
-}
 
-impl Destination {
-    /// Returns a `Write` corresponding to the `Destination`.
-    fn write(&self) -> Box<Write> {
-        match *self {
-            Destination::Stdout => Box::new(stdout()),
-            Destination::Stderr => Box::new(stderr()),
-        }
+    /// Opens the destination's underlying writer.
+    ///
+    /// For `File`, this opens the file once; the caller is expected to
+    /// cache and reuse the result rather than calling this per record.
+    ///
+    /// `Stdout`/`Stderr` are wrapped in a `termcolor::StandardStream`, which
+    /// picks ANSI escapes, the Windows console API, or plain text as
+    /// appropriate for the underlying console. `File` and `Writer` are
+    /// never colored, since there's no console to detect capabilities of.
+    fn open(&self) -> io::Result<Arc<Mutex<dyn WriteColor + Send>>> {
+        Ok(match *self {
+            Destination::Stdout => {
+                Arc::new(Mutex::new(StandardStream::stdout(ColorChoice::Auto)))
+            }
+            Destination::Stderr => {
+                Arc::new(Mutex::new(StandardStream::stderr(ColorChoice::Auto)))
+            }
+            Destination::File(ref path) => {
+                let file = OpenOptions::new().create(true).append(true).open(
+                    path,
+                )?;
+                Arc::new(Mutex::new(NoColor::new(file)))
+            }
+            Destination::Writer(ref writer) => {
+                Arc::new(Mutex::new(NoColor::new(SharedWriter(writer.clone()))))
+            }
+        })
     }
 }
 
@@ -66,10 +158,14 @@ impl Default for Destination {
 ///  - Use color iff `stderr` is a TTY
 pub struct Logger {
     destination: Destination,
-    level: LevelFilter,
+    filter: Filter,
+    format: Format,
     max_module_width: AtomicUsize,
     max_target_width: AtomicUsize,
+    target_colors: Mutex<HashMap<String, Style>>,
     theme: Theme,
+    timestamp: Option<TimestampFormat>,
+    writer: Mutex<Option<Arc<Mutex<dyn WriteColor + Send>>>>,
 }
 
 impl Logger {
@@ -78,16 +174,62 @@ impl Logger {
         destination: Destination,
         level: LevelFilter,
         theme: Theme,
+    ) -> Logger {
+        Logger::with_filter(destination, Filter::from_level(level), theme)
+    }
+
+    /// Creates a new instance of Logger with per-module filtering.
+    pub fn with_filter(
+        destination: Destination,
+        filter: Filter,
+        theme: Theme,
     ) -> Logger {
         Logger {
             destination,
-            level,
+            filter,
+            format: Format::default(),
             max_module_width: AtomicUsize::new(0),
             max_target_width: AtomicUsize::new(0),
+            target_colors: Mutex::new(HashMap::new()),
             theme,
+            timestamp: None,
+            writer: Mutex::new(None),
         }
     }
 
+    /// Returns the writer for this logger's destination, opening and
+    /// caching it on first use. If the destination is a file that fails to
+    /// open, falls back to `stderr`.
+    fn writer(&self) -> Arc<Mutex<dyn WriteColor + Send>> {
+        let mut cache = self.writer.lock().unwrap();
+        if cache.is_none() {
+            *cache = Some(self.destination.open().unwrap_or_else(|_| {
+                Arc::new(Mutex::new(StandardStream::stderr(ColorChoice::Auto)))
+            }));
+        }
+        cache.as_ref().unwrap().clone()
+    }
+
+    /// Enables a timestamp prefix on each log line, rendered with the given
+    /// format.
+    pub fn with_timestamp(mut self, format: TimestampFormat) -> Logger {
+        self.timestamp = Some(format);
+        self
+    }
+
+    /// Sets the line format to use, in place of the default
+    /// `{level}|{module}|{args}` layout.
+    pub fn with_format(mut self, format: Format) -> Logger {
+        self.format = format;
+        self
+    }
+
+    /// Returns a [`LoggerBuilder`](struct.LoggerBuilder.html) for
+    /// incrementally configuring a `Logger`.
+    pub fn builder() -> LoggerBuilder {
+        LoggerBuilder::new()
+    }
+
     /// Sets this logger as the global logger.
     pub fn set_logger(self) -> Result<(), SetLoggerError> {
         log::set_boxed_logger(Box::new(self))
@@ -122,6 +264,19 @@ impl Logger {
             }
         }
     }
+
+    /// Returns the style to render `target` with, computing and caching it
+    /// on first use so the same target always gets the same color.
+    fn target_style(&self, target: &str) -> Style {
+        let mut cache = self.target_colors.lock().unwrap();
+        if let Some(style) = cache.get(target) {
+            return *style;
+        }
+
+        let style = self.theme.target_style(target);
+        cache.insert(target.to_string(), style);
+        style
+    }
 }
 
 impl Default for Logger {
@@ -138,10 +293,7 @@ impl Default for Logger {
 
 impl Log for Logger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        self.level
-            .to_level()
-            .map(|level| metadata.level() <= level)
-            .unwrap_or(false)
+        self.filter.enabled(metadata.target(), metadata.level())
     }
 
     fn flush(&self) {}
@@ -153,32 +305,63 @@ impl Log for Logger {
 
         let module = record.module_path().unwrap_or("<unknown>");
         let target = record.target();
-        let module_length =
-            self.update_module_width(module.graphemes(true).count());
-
-        let _ = if module == target {
-            writeln!(
-                self.destination.write(),
-                "{}|{:.*}|{}",
-                self.theme.paint_log_level(record.level()),
-                module_length,
-                module,
-                record.args()
-            )
-        } else {
-            let target_length =
-                self.update_target_width(target.graphemes(true).count());
-            writeln!(
-                self.destination.write(),
-                "{}|{:.*}|{:.*}|{}",
-                self.theme.paint_log_level(record.level()),
-                module_length,
-                module,
-                target_length,
-                target,
-                record.args()
-            )
-        };
+
+        let writer = self.writer();
+        let mut writer = writer.lock().unwrap();
+
+        for piece in self.format.pieces() {
+            let _ = match *piece {
+                FormatPiece::Timestamp => {
+                    if let Some(ref format) = self.timestamp {
+                        color::write_styled(
+                            &mut *writer,
+                            self.theme.timestamp,
+                            &format.format_now(),
+                        ).and_then(|_| write!(*writer, "|"))
+                    } else {
+                        Ok(())
+                    }
+                }
+                FormatPiece::Level => {
+                    let (style, name) = self.theme.level_style(record.level());
+                    color::write_styled(&mut *writer, style, name)
+                }
+                FormatPiece::Module => {
+                    let length = module.graphemes(true).count();
+                    let width = self.update_module_width(length);
+                    color::write_styled(
+                        &mut *writer,
+                        self.theme.module,
+                        &format!("{}{}", module, " ".repeat(width - length)),
+                    )
+                }
+                FormatPiece::Target => {
+                    if target != module {
+                        let width = self.update_target_width(
+                            target.graphemes(true).count(),
+                        );
+                        if self.theme.colored_targets {
+                            let style = self.target_style(target);
+                            write!(*writer, "|").and_then(|_| {
+                                color::write_styled(
+                                    &mut *writer,
+                                    style,
+                                    &format!("{:.*}", width, target),
+                                )
+                            })
+                        } else {
+                            write!(*writer, "|{:.*}", width, target)
+                        }
+                    } else {
+                        Ok(())
+                    }
+                }
+                FormatPiece::Args => write!(*writer, "{}", record.args()),
+                FormatPiece::Literal(ref literal) => write!(*writer, "{}", literal),
+            };
+        }
+
+        let _ = writeln!(*writer);
     }
 }
 
@@ -192,6 +375,9 @@ impl Log for Logger {
 ///  - `DEBUG` printed in gray.
 ///  - `TRACE` printed in dimmed gray.
 ///  - The module name is not styled.
+///  - The timestamp, if enabled, is not styled.
+///  - Targets that differ from their module are colored, each getting a
+///    stable color chosen by hashing its name.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Theme {
     /// The style to give the "ERROR" string.
@@ -211,6 +397,14 @@ pub struct Theme {
 
     /// The style to give the module name.
     pub module: Style,
+
+    /// The style to give the timestamp, when enabled.
+    pub timestamp: Style,
+
+    /// Whether to color the target column (when it's shown) with a color
+    /// chosen by hashing the target's name, so the same target is always
+    /// the same color within a run. Set to `false` to leave it unstyled.
+    pub colored_targets: bool,
 }
 
 impl Theme {
@@ -223,22 +417,76 @@ impl Theme {
             debug: Style::new(),
             trace: Style::new(),
             module: Style::new(),
+            timestamp: Style::new(),
+            colored_targets: false,
         }
     }
 
-    /// Paints a log level with a theme.
-    pub fn paint_log_level(
+    /// Writes a log level's styled name to `writer`.
+    ///
+    /// This goes through the same `termcolor` abstraction `Logger` uses
+    /// internally, rather than painting raw ANSI bytes, so it renders
+    /// correctly across terminals (including Windows consoles that don't
+    /// understand ANSI escapes).
+    pub fn paint_log_level<W: WriteColor + ?Sized>(
         &self,
+        writer: &mut W,
         level: Level,
-    ) -> ANSIGenericString<'static, str> {
-        let (style, name) = match level {
+    ) -> io::Result<()> {
+        let (style, name) = self.level_style(level);
+        color::write_styled(writer, style, name)
+    }
+
+    /// Returns the style and fixed-width name to use for a log level.
+    fn level_style(&self, level: Level) -> (Style, &'static str) {
+        match level {
             Level::Error => (self.error, "ERROR"),
             Level::Warn => (self.warn, "WARN "),
             Level::Info => (self.info, "INFO "),
             Level::Debug => (self.debug, "DEBUG"),
             Level::Trace => (self.trace, "TRACE"),
-        };
-        style.paint(name)
+        }
+    }
+
+    /// Returns the style to give `target`, if `colored_targets` is enabled.
+    ///
+    /// The color is chosen by hashing `target` into the 8 ANSI colors, minus
+    /// whichever are already used by a log level, so targets are unlikely
+    /// to be confused with levels and the same target always gets the same
+    /// color.
+    fn target_style(&self, target: &str) -> Style {
+        if !self.colored_targets {
+            return Style::new();
+        }
+
+        let palette = self.target_palette();
+        if palette.is_empty() {
+            return Style::new();
+        }
+
+        let mut hasher = DefaultHasher::new();
+        target.hash(&mut hasher);
+        let index = (hasher.finish() as usize) % palette.len();
+        palette[index].normal()
+    }
+
+    /// Returns the colors available for `target_style` to choose from.
+    fn target_palette(&self) -> Vec<Colour> {
+        let used: Vec<Colour> = [
+            self.error,
+            self.warn,
+            self.info,
+            self.debug,
+            self.trace,
+        ].iter()
+            .filter_map(|style| style.foreground)
+            .collect();
+
+        PALETTE
+            .iter()
+            .cloned()
+            .filter(|colour| !used.contains(colour))
+            .collect()
     }
 }
 
@@ -251,6 +499,8 @@ impl Default for Theme {
             debug: Colour::White.normal(),
             trace: Colour::White.dimmed(),
             module: Style::new(),
+            timestamp: Style::new(),
+            colored_targets: true,
         }
     }
 }
@@ -270,7 +520,19 @@ pub fn init(
 pub fn init_level(level: LevelFilter) -> Result<(), SetLoggerError> {
     platform_init();
     let mut logger = Logger::default();
-    logger.level = level;
+    logger.filter = Filter::from_level(level);
+    logger.set_logger()
+}
+
+/// Initializes the global logger using a directive string to configure
+/// per-module filtering, using the defaults for other fields.
+///
+/// Directive strings look like `my_crate=debug,my_crate::net=trace,warn`;
+/// see [`Filter`](struct.Filter.html) for the full syntax.
+pub fn init_from_str(spec: &str) -> Result<(), SetLoggerError> {
+    platform_init();
+    let mut logger = Logger::default();
+    logger.filter = Filter::parse(spec);
     logger.set_logger()
 }
 
@@ -280,11 +542,25 @@ pub fn init_to_defaults() -> Result<(), SetLoggerError> {
     Logger::default().set_logger()
 }
 
-#[cfg(windows)]
-fn platform_init() {
-    use ansi_term::enable_ansi_support;
-    let _ = enable_ansi_support();
+/// Initializes the global logger at a level chosen by an occurrence count,
+/// using the defaults for other fields.
+///
+/// This matches the common CLI idiom of raising verbosity with repeated
+/// `-v` flags: `0` maps to `Warn`, `1` to `Info`, `2` to `Debug`, and `3` or
+/// more to `Trace`.
+pub fn init_verbosity(verbosity: u64) -> Result<(), SetLoggerError> {
+    let level = match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    init_level(level)
 }
 
-#[cfg(not(windows))]
-fn platform_init() {}
+/// Platform-specific setup run before a logger is registered.
+///
+/// Color no longer needs to be specially enabled here: `Destination::open`
+/// routes `Stdout`/`Stderr` through `termcolor`, which detects console
+/// capabilities (including the Windows console API) itself.
+pub(crate) fn platform_init() {}
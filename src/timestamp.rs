@@ -0,0 +1,67 @@
+//! Timestamp formatting for log lines.
+
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::Local;
+
+/// The format used to render the timestamp prefixed to each log line.
+///
+/// Timestamps are disabled by default; set
+/// [`Logger::with_timestamp`](struct.Logger.html#method.with_timestamp) to
+/// enable one of these.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TimestampFormat {
+    /// RFC 3339, e.g. `2018-02-13T23:08:15.123456789+00:00`.
+    Rfc3339,
+
+    /// A short `HH:MM:SS` time, with no date.
+    ShortTime,
+
+    /// Seconds since the Unix epoch.
+    Epoch,
+
+    /// A custom `strftime`-style pattern, as accepted by `chrono`.
+    Custom(String),
+}
+
+impl TimestampFormat {
+    /// Renders the current time using this format.
+    pub fn format_now(&self) -> String {
+        match *self {
+            TimestampFormat::Rfc3339 => Local::now().to_rfc3339(),
+            TimestampFormat::ShortTime => {
+                Local::now().format("%H:%M:%S").to_string()
+            }
+            TimestampFormat::Epoch => {
+                let since_epoch = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                since_epoch.as_secs().to_string()
+            }
+            TimestampFormat::Custom(ref pattern) => {
+                // `DelayedFormat`'s `Display` impl returns an `fmt::Error`
+                // for a pattern with an invalid specifier, and `to_string`
+                // panics on that; write to a buffer and fall back to the
+                // raw pattern instead of crashing the process.
+                let mut rendered = String::new();
+                match write!(rendered, "{}", Local::now().format(pattern)) {
+                    Ok(()) => rendered,
+                    Err(_) => pattern.clone(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_custom_pattern_falls_back_instead_of_panicking() {
+        let format = TimestampFormat::Custom("%_bad_".to_owned());
+
+        assert_eq!(format.format_now(), "%_bad_");
+    }
+}